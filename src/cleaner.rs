@@ -0,0 +1,297 @@
+//! Typography cleaner
+//!
+//! A post-processing pass that turns "dumb" ASCII punctuation into its
+//! typographically correct form: curly quotes, en/em dashes, and ellipses,
+//! plus (for [`Typography::French`]) narrow no-break spaces around certain
+//! punctuation. Runs on the fully rendered HTML and skips text inside
+//! `<code>`, `<pre>`, and plugin output (`class="plugin-*"`), so source
+//! code and plugin-rendered markup are never rewritten.
+
+/// Typography profile controlling how [`clean`] rewrites punctuation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Typography {
+    /// No typographic substitutions; `clean` is a no-op.
+    #[default]
+    None,
+    /// Curly quotes, en/em dashes, and ellipses (English conventions).
+    Default,
+    /// Everything [`Typography::Default`] does, plus a narrow no-break
+    /// space (U+202F) before `?`, `!`, `;`, `:` and inside `« »` guillemets.
+    French,
+}
+
+/// Narrow no-break space (U+202F), used by [`Typography::French`].
+const NNBSP: char = '\u{202F}';
+
+/// Apply `profile`'s typographic substitutions to the text runs of `html`,
+/// leaving markup, `<code>`/`<pre>` content, and plugin output untouched.
+/// A no-op when `profile` is [`Typography::None`].
+pub fn clean(html: &str, profile: Typography) -> String {
+    if profile == Typography::None {
+        return html.to_string();
+    }
+
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut code_depth: u32 = 0;
+    let mut plugin_depth: u32 = 0;
+    let mut wrapper_stack: Vec<bool> = Vec::new();
+
+    while let Some(lt) = rest.find('<') {
+        let text = &rest[..lt];
+        if code_depth == 0 && plugin_depth == 0 {
+            out.push_str(&apply_profile(text, profile));
+        } else {
+            out.push_str(text);
+        }
+
+        let after = &rest[lt..];
+        let gt = after.find('>').map_or(after.len(), |i| i + 1);
+        let tag = &after[..gt];
+        out.push_str(tag);
+        update_skip_depth(tag, &mut code_depth, &mut plugin_depth, &mut wrapper_stack);
+        rest = &after[gt..];
+    }
+
+    if code_depth == 0 && plugin_depth == 0 {
+        out.push_str(&apply_profile(rest, profile));
+    } else {
+        out.push_str(rest);
+    }
+
+    out
+}
+
+/// Update the `<code>`/`<pre>` and plugin-output skip counters for `tag`.
+///
+/// `wrapper_stack` tracks, per currently-open `<div>`/`<span>`, whether
+/// *that particular* tag was a plugin wrapper (`class="plugin-*"`) — so a
+/// `</div>`/`</span>` only decrements `plugin_depth` when it closes a
+/// wrapper tag, not whenever any nested `<div>`/`<span>` inside plugin
+/// output happens to close. A blind "decrement on any closing tag" counter
+/// would zero out `plugin_depth` early if plugin output ever nested an
+/// unrelated `<div>`/`<span>` pair, letting typography substitutions leak
+/// into content meant to be protected.
+fn update_skip_depth(
+    tag: &str,
+    code_depth: &mut u32,
+    plugin_depth: &mut u32,
+    wrapper_stack: &mut Vec<bool>,
+) {
+    let lower = tag.to_ascii_lowercase();
+    let Some((name, closing)) = tag_name(&lower) else {
+        return;
+    };
+
+    if name == "code" || name == "pre" {
+        if closing {
+            *code_depth = code_depth.saturating_sub(1);
+        } else {
+            *code_depth += 1;
+        }
+        return;
+    }
+
+    if name != "div" && name != "span" {
+        return;
+    }
+    if is_self_closing(&lower) {
+        return; // no open/close pair to track
+    }
+
+    if closing {
+        if wrapper_stack.pop() == Some(true) {
+            *plugin_depth = plugin_depth.saturating_sub(1);
+        }
+    } else {
+        let is_plugin_wrapper =
+            lower.contains("class=\"plugin-") || lower.contains("class='plugin-");
+        wrapper_stack.push(is_plugin_wrapper);
+        if is_plugin_wrapper {
+            *plugin_depth += 1;
+        }
+    }
+}
+
+/// Whether a (lowercased) opening tag is self-closing (`<div ... />`) and
+/// so has no matching close to track.
+fn is_self_closing(tag_lower: &str) -> bool {
+    tag_lower
+        .trim_end_matches('>')
+        .trim_end()
+        .ends_with('/')
+}
+
+/// Extract `(tag_name, is_closing)` from a lowercased tag source, e.g.
+/// `"<code>"` -> `("code", false)`, `"</pre>"` -> `("pre", true)`.
+fn tag_name(tag_lower: &str) -> Option<(&str, bool)> {
+    let closing = tag_lower.starts_with("</");
+    let body = if closing { &tag_lower[2..] } else { &tag_lower[1..] };
+    let end = body
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(body.len());
+    if end == 0 {
+        return None;
+    }
+    Some((&body[..end], closing))
+}
+
+fn apply_profile(text: &str, profile: Typography) -> String {
+    let text = replace_dashes_and_ellipsis(text);
+    let text = curl_quotes(&text);
+    match profile {
+        Typography::French => insert_french_spacing(&text),
+        _ => text,
+    }
+}
+
+fn replace_dashes_and_ellipsis(text: &str) -> String {
+    text.replace("---", "—")
+        .replace("--", "–")
+        .replace("...", "…")
+}
+
+/// Replace straight `"`/`'` with curly quotes, guessing open vs. close from
+/// the surrounding characters (a preceding letter/digit means `'` is an
+/// apostrophe, not an opening quote).
+fn curl_quotes(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut prev: Option<char> = None;
+
+    for (i, &ch) in chars.iter().enumerate() {
+        match ch {
+            '"' => {
+                let opening = prev.is_none_or(|c| c.is_whitespace() || "([{-—–“".contains(c));
+                out.push(if opening { '“' } else { '”' });
+            }
+            '\'' => {
+                if prev.is_some_and(|c| c.is_alphanumeric()) {
+                    out.push('’'); // apostrophe, e.g. "don't"
+                } else {
+                    let opening = chars.get(i + 1).is_some_and(|c| !c.is_whitespace());
+                    out.push(if opening { '‘' } else { '’' });
+                }
+            }
+            _ => out.push(ch),
+        }
+        prev = Some(ch);
+    }
+
+    out
+}
+
+/// Insert a narrow no-break space before `? ! ; :` and just inside
+/// `« »` guillemets, per French typographic convention.
+fn insert_french_spacing(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + 8);
+
+    for (i, &ch) in chars.iter().enumerate() {
+        match ch {
+            '?' | '!' | ';' | ':' => {
+                // The `;` ending a character reference (`&amp;`, `&#39;`) is
+                // markup, not punctuation.
+                let name_len = chars[..i]
+                    .iter()
+                    .rev()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '#')
+                    .count();
+                let closes_entity =
+                    ch == ';' && name_len > 0 && i > name_len && chars[i - name_len - 1] == '&';
+                let needs_space = !closes_entity
+                    && i.checked_sub(1)
+                    .map(|p| chars[p])
+                    .is_some_and(|c| !c.is_whitespace() && c != NNBSP);
+                if needs_space {
+                    out.push(NNBSP);
+                }
+                out.push(ch);
+            }
+            '«' => {
+                out.push(ch);
+                if chars.get(i + 1).is_some_and(|c| !c.is_whitespace() && *c != NNBSP) {
+                    out.push(NNBSP);
+                }
+            }
+            '»' => {
+                let needs_space = i
+                    .checked_sub(1)
+                    .map(|p| chars[p])
+                    .is_some_and(|c| !c.is_whitespace() && c != NNBSP);
+                if needs_space {
+                    out.push(NNBSP);
+                }
+                out.push(ch);
+            }
+            _ => out.push(ch),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_none_is_noop() {
+        let html = "<p>\"Hello\" -- world...</p>";
+        assert_eq!(clean(html, Typography::None), html);
+    }
+
+    #[test]
+    fn test_default_curls_quotes() {
+        let out = clean("<p>\"Hello\"</p>", Typography::Default);
+        assert_eq!(out, "<p>“Hello”</p>");
+    }
+
+    #[test]
+    fn test_default_apostrophe() {
+        let out = clean("<p>don't</p>", Typography::Default);
+        assert_eq!(out, "<p>don’t</p>");
+    }
+
+    #[test]
+    fn test_default_dashes_and_ellipsis() {
+        let out = clean("<p>a -- b --- c...</p>", Typography::Default);
+        assert_eq!(out, "<p>a – b — c…</p>");
+    }
+
+    #[test]
+    fn test_skips_code_and_pre() {
+        let html = "<p>\"quoted\"</p><pre>\"raw\"</pre><code>\"raw\"</code>";
+        let out = clean(html, Typography::Default);
+        assert_eq!(
+            out,
+            "<p>“quoted”</p><pre>\"raw\"</pre><code>\"raw\"</code>"
+        );
+    }
+
+    #[test]
+    fn test_skips_plugin_output() {
+        let html = r#"<div class="plugin-code" data-args='["rust"]'>"raw"</div>"#;
+        assert_eq!(clean(html, Typography::Default), html);
+    }
+
+    #[test]
+    fn test_skips_plugin_output_with_nested_non_plugin_tags() {
+        // A nested <span>...</span> that isn't itself a plugin wrapper
+        // shouldn't prematurely end the skip region for the outer one.
+        let html = r#"<div class="plugin-highlight"><span>"raw"</span> more "raw"</div>"#;
+        assert_eq!(clean(html, Typography::Default), html);
+    }
+
+    #[test]
+    fn test_french_spacing() {
+        let out = clean("<p>Vraiment?</p>", Typography::French);
+        assert_eq!(out, "<p>Vraiment\u{202F}?</p>");
+    }
+
+    #[test]
+    fn test_french_guillemets() {
+        let out = clean("<p>«bonjour»</p>", Typography::French);
+        assert_eq!(out, "<p>«\u{202F}bonjour\u{202F}»</p>");
+    }
+}
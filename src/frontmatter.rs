@@ -0,0 +1,107 @@
+//! Frontmatter extraction
+//!
+//! Parses the optional YAML-style `---` delimited block at the start of a
+//! document, as used by static site generators and most wiki exports.
+
+use std::collections::HashMap;
+
+/// Parsed frontmatter key/value pairs.
+///
+/// Values are kept as raw strings; callers that need typed data (dates,
+/// lists, ...) are expected to parse the fields they care about themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    fields: HashMap<String, String>,
+}
+
+impl Frontmatter {
+    /// Look up a field by key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    /// Iterate over all parsed fields.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+/// Extract a leading `---`/`---` frontmatter block from `input`.
+///
+/// Returns the parsed frontmatter (`None` if the document doesn't start
+/// with a frontmatter block) and the remaining content with that block
+/// removed.
+///
+/// # Examples
+///
+/// ```
+/// use lukiwiki_parser::frontmatter::extract_frontmatter;
+///
+/// let (fm, content) = extract_frontmatter("---\ntitle: Test\n---\n\n# Hi");
+/// assert_eq!(fm.unwrap().get("title"), Some("Test"));
+/// assert_eq!(content.trim(), "# Hi");
+/// ```
+pub fn extract_frontmatter(input: &str) -> (Option<Frontmatter>, String) {
+    let mut lines = input.lines();
+
+    match lines.next() {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return (None, input.to_string()),
+    }
+
+    let mut fields = HashMap::new();
+    let mut body_start = None;
+    let mut consumed_lines = 1;
+
+    for line in lines {
+        consumed_lines += 1;
+        if line.trim_end() == "---" {
+            body_start = Some(consumed_lines);
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            fields.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    match body_start {
+        Some(skip) => {
+            let rest: String = input
+                .lines()
+                .skip(skip)
+                .collect::<Vec<_>>()
+                .join("\n");
+            (Some(Frontmatter { fields }), rest)
+        }
+        // Unterminated block: treat the whole thing as plain content.
+        None => (None, input.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_frontmatter() {
+        let (fm, content) = extract_frontmatter("# Hello");
+        assert!(fm.is_none());
+        assert_eq!(content, "# Hello");
+    }
+
+    #[test]
+    fn test_with_frontmatter() {
+        let (fm, content) = extract_frontmatter("---\ntitle: Test\nauthor: Alice\n---\n\n# Content");
+        let fm = fm.unwrap();
+        assert_eq!(fm.get("title"), Some("Test"));
+        assert_eq!(fm.get("author"), Some("Alice"));
+        assert!(content.contains("# Content"));
+    }
+
+    #[test]
+    fn test_unterminated_block_is_not_frontmatter() {
+        let (fm, content) = extract_frontmatter("---\ntitle: Test\n\n# Content");
+        assert!(fm.is_none());
+        assert!(content.starts_with("---"));
+    }
+}
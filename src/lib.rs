@@ -40,6 +40,7 @@
 
 use wasm_bindgen::prelude::*;
 
+pub mod cleaner;
 pub mod frontmatter;
 pub mod lukiwiki;
 pub mod parser;
@@ -52,6 +53,15 @@ pub struct ParseResult {
     pub html: String,
     /// Optional frontmatter data
     pub frontmatter: Option<frontmatter::Frontmatter>,
+    /// Headings collected while assigning anchor IDs, in document order.
+    ///
+    /// Useful for building a sidebar or table of contents outside of the
+    /// `@toc()` plugin.
+    pub headings: Vec<parser::Heading>,
+    /// Original URLs of remote images rewritten through
+    /// `options.image_proxy`, in document order. Empty unless
+    /// [`parser::ParserOptions::image_proxy`] was set.
+    pub rewritten_image_urls: Vec<String>,
 }
 
 /// Parse LukiWiki markup and convert to HTML
@@ -73,7 +83,7 @@ pub struct ParseResult {
 ///
 /// let input = "# Heading\n\n**Bold** and *italic*";
 /// let html = parse(input);
-/// assert!(html.contains("<h1>"));
+/// assert!(html.contains("<h1 id=\"heading\">"));
 /// assert!(html.contains("<strong>"));
 /// ```
 pub fn parse(input: &str) -> String {
@@ -101,28 +111,64 @@ pub fn parse(input: &str) -> String {
 /// let input = "---\ntitle: Test\n---\n\n# Content";
 /// let result = parse_with_frontmatter(input);
 /// assert!(result.frontmatter.is_some());
-/// assert!(result.html.contains("<h1>"));
+/// assert!(result.html.contains("<h1 id=\"content\">"));
 /// ```
 pub fn parse_with_frontmatter(input: &str) -> ParseResult {
+    parse_with_frontmatter_opts(input, &parser::ParserOptions::default())
+}
+
+/// Parse LukiWiki markup and return HTML with frontmatter, using caller-supplied
+/// [`parser::ParserOptions`] instead of the defaults.
+///
+/// This is the entry point for turning on [`parser::ParserOptions::html_mode`],
+/// [`parser::ParserOptions::image_proxy`], or [`parser::ParserOptions::typography`] —
+/// [`parse`] and [`parse_with_frontmatter`] always run with
+/// [`parser::ParserOptions::default`].
+///
+/// # Examples
+///
+/// ```
+/// use lukiwiki_parser::parse_with_frontmatter_opts;
+/// use lukiwiki_parser::parser::ParserOptions;
+///
+/// let options = ParserOptions {
+///     image_proxy: Some("https://proxy.example/img".to_string()),
+///     ..ParserOptions::default()
+/// };
+/// let result = parse_with_frontmatter_opts("![alt](https://example.com/a.png)", &options);
+/// assert!(result.html.contains("https://proxy.example/img?url="));
+/// ```
+pub fn parse_with_frontmatter_opts(input: &str, options: &parser::ParserOptions) -> ParseResult {
     // Step 0: Extract frontmatter
     let (frontmatter_data, content) = frontmatter::extract_frontmatter(input);
 
     // Step 1: Pre-process to resolve syntax conflicts (before sanitization)
     let preprocessed = lukiwiki::conflict_resolver::preprocess_conflicts(&content);
 
-    // Step 2: Sanitize input
-    let sanitized = sanitizer::sanitize(&preprocessed);
+    // Step 2: Sanitize input (escape-all by default, or allowlist passthrough
+    // when `options.html_mode` opts in)
+    let sanitized = sanitizer::sanitize_with(&preprocessed, options.html_mode);
 
-    // Step 3: Parse with comrak-based parser
-    let options = parser::ParserOptions::default();
-    let html = parser::parse_to_html(&sanitized, &options);
+    // Step 3: Parse with comrak-based parser, collecting heading anchors
+    let (html, headings) = parser::parse_to_html_with_headings(&sanitized, options);
 
     // Step 4: Apply LukiWiki-specific syntax (includes post-processing)
-    let final_html = lukiwiki::apply_lukiwiki_syntax(&html);
+    let final_html = lukiwiki::apply_lukiwiki_syntax(&html, &headings, options);
+
+    // Step 4.5: Typographic cleanup (smart quotes, dashes, ellipses, ...)
+    let final_html = cleaner::clean(&final_html, options.typography);
+
+    // Step 5: Rewrite remote image URLs through `options.image_proxy`, if set
+    let (final_html, rewritten_image_urls) = match &options.image_proxy {
+        Some(proxy_base) => parser::rewrite_image_proxies(&final_html, proxy_base),
+        None => (final_html, Vec::new()),
+    };
 
     ParseResult {
         html: final_html,
         frontmatter: frontmatter_data,
+        headings,
+        rewritten_image_urls,
     }
 }
 
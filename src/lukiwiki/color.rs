@@ -0,0 +1,241 @@
+//! `COLOR()` syntax rendering
+//!
+//! Maps a semantic keyword (`success`, `danger`, ...) to Bootstrap-style
+//! `text-*`/`bg-*` utility classes (checked first, as a fast path), and
+//! falls back to a literal CSS color (`#RGB`, `rgb()`, `hsl()`, or a named
+//! color) emitted as an inline `style` attribute. A literal value is only
+//! trusted once it's been strictly validated against one of those
+//! grammars; anything else renders as plain text, so `style`-injection
+//! payloads like `red; background:url(...)` can never reach the output.
+//!
+//! `text` is raw wikitext captured before sanitization (see
+//! [`crate::lukiwiki::conflict_resolver`]), so it's escaped before being
+//! embedded, the same as `value`.
+
+/// Render a `COLOR(value): text` invocation.
+pub(crate) fn render(value: &str, text: &str) -> String {
+    let value = value.trim();
+    let text = crate::sanitizer::sanitize(text);
+
+    if let Some(html) = render_semantic(value, &text) {
+        return html;
+    }
+    if is_valid_css_color(value) {
+        return format!("<span style=\"color: {value}\">{text}</span>");
+    }
+    format!("COLOR({}):{text}", crate::sanitizer::sanitize(value))
+}
+
+fn render_semantic(value: &str, text: &str) -> Option<String> {
+    if !is_semantic_keyword(value) {
+        return None;
+    }
+    Some(format!(
+        "<span class=\"text-{value} bg-{value}\">{text}</span>"
+    ))
+}
+
+fn is_semantic_keyword(value: &str) -> bool {
+    matches!(
+        value,
+        "primary" | "secondary" | "success" | "danger" | "warning" | "info" | "light" | "dark"
+    )
+}
+
+/// Validate `value` against one of the literal CSS color grammars this
+/// crate supports. Anything that doesn't fully match is rejected.
+fn is_valid_css_color(value: &str) -> bool {
+    is_valid_hex(value) || is_valid_rgb(value) || is_valid_hsl(value) || is_named_color(value)
+}
+
+fn is_valid_hex(value: &str) -> bool {
+    let Some(hex) = value.strip_prefix('#') else {
+        return false;
+    };
+    matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// `rgb(r, g, b)` / `rgba(r, g, b, a)`, each of `r`/`g`/`b` an integer
+/// `0..=255` or a percentage `0%..=100%`, `a` a number `0..=1` or a
+/// percentage.
+fn is_valid_rgb(value: &str) -> bool {
+    let (inner, has_alpha) = match strip_function(value, "rgba") {
+        Some(inner) => (inner, true),
+        None => match strip_function(value, "rgb") {
+            Some(inner) => (inner, false),
+            None => return false,
+        },
+    };
+
+    let parts: Vec<&str> = inner.split(',').map(str::trim).collect();
+    if parts.len() != if has_alpha { 4 } else { 3 } {
+        return false;
+    }
+
+    parts[..3].iter().all(|p| is_rgb_component(p)) && (!has_alpha || is_alpha_component(parts[3]))
+}
+
+/// `hsl(h, s%, l%)` / `hsla(h, s%, l%, a)`, `h` a number `0..=360`
+/// (optionally suffixed `deg`), `s`/`l` percentages, `a` as in
+/// [`is_valid_rgb`].
+fn is_valid_hsl(value: &str) -> bool {
+    let (inner, has_alpha) = match strip_function(value, "hsla") {
+        Some(inner) => (inner, true),
+        None => match strip_function(value, "hsl") {
+            Some(inner) => (inner, false),
+            None => return false,
+        },
+    };
+
+    let parts: Vec<&str> = inner.split(',').map(str::trim).collect();
+    if parts.len() != if has_alpha { 4 } else { 3 } {
+        return false;
+    }
+
+    is_valid_hue(parts[0])
+        && is_percentage_in_range(parts[1])
+        && is_percentage_in_range(parts[2])
+        && (!has_alpha || is_alpha_component(parts[3]))
+}
+
+/// Strip a `name(` prefix and trailing `)`, requiring an exact match (no
+/// extra characters before `(` or after `)`).
+fn strip_function<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    value
+        .strip_prefix(name)?
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+fn is_rgb_component(s: &str) -> bool {
+    if let Some(pct) = s.strip_suffix('%') {
+        return is_number_in_range(pct, 0.0, 100.0);
+    }
+    !s.contains('.') && is_number_in_range(s, 0.0, 255.0)
+}
+
+fn is_alpha_component(s: &str) -> bool {
+    if let Some(pct) = s.strip_suffix('%') {
+        return is_number_in_range(pct, 0.0, 100.0);
+    }
+    is_number_in_range(s, 0.0, 1.0)
+}
+
+fn is_percentage_in_range(s: &str) -> bool {
+    s.strip_suffix('%')
+        .is_some_and(|pct| is_number_in_range(pct, 0.0, 100.0))
+}
+
+fn is_valid_hue(s: &str) -> bool {
+    is_number_in_range(s.strip_suffix("deg").unwrap_or(s), 0.0, 360.0)
+}
+
+/// Whether `s` is a plain (no sign, no exponent) decimal number in
+/// `min..=max`.
+fn is_number_in_range(s: &str, min: f64, max: f64) -> bool {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return false;
+    }
+    s.parse::<f64>().is_ok_and(|n| (min..=max).contains(&n))
+}
+
+const NAMED_COLORS: &[&str] = &[
+    "black", "white", "red", "green", "blue", "yellow", "cyan", "magenta", "gray", "grey",
+    "orange", "purple", "pink", "brown", "silver", "gold", "maroon", "navy", "teal", "olive",
+    "lime", "indigo", "violet", "coral", "salmon", "khaki", "crimson", "chocolate", "beige",
+    "ivory", "lavender", "turquoise", "tan", "plum", "orchid", "skyblue", "slateblue", "tomato",
+];
+
+fn is_named_color(value: &str) -> bool {
+    NAMED_COLORS.contains(&value.to_ascii_lowercase().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semantic_keyword() {
+        let html = render("success", " This is a success message");
+        assert!(html.contains("text-success"));
+        assert!(html.contains("bg-success"));
+    }
+
+    #[test]
+    fn test_hex_colors() {
+        for value in ["#f00", "#ff0000", "#f00a", "#ff0000aa"] {
+            assert!(is_valid_css_color(value), "{value} should be valid");
+        }
+        assert!(!is_valid_css_color("#ff0000a")); // 7 hex digits is not a valid length
+        assert!(!is_valid_css_color("#gggggg"));
+    }
+
+    #[test]
+    fn test_rgb_and_rgba() {
+        assert!(is_valid_css_color("rgb(255, 0, 0)"));
+        assert!(is_valid_css_color("rgba(255, 0, 0, 0.5)"));
+        assert!(is_valid_css_color("rgb(100%, 0%, 0%)"));
+        assert!(!is_valid_css_color("rgb(256, 0, 0)"));
+        assert!(!is_valid_css_color("rgba(255, 0, 0, 1.5)"));
+        assert!(!is_valid_css_color("rgb(255, 0)"));
+    }
+
+    #[test]
+    fn test_hsl_and_hsla() {
+        assert!(is_valid_css_color("hsl(120, 50%, 50%)"));
+        assert!(is_valid_css_color("hsla(120deg, 50%, 50%, 0.8)"));
+        assert!(!is_valid_css_color("hsl(400, 50%, 50%)"));
+        assert!(!is_valid_css_color("hsl(120, 50, 50%)"));
+    }
+
+    #[test]
+    fn test_named_colors() {
+        assert!(is_valid_css_color("red"));
+        assert!(is_valid_css_color("SkyBlue"));
+        assert!(!is_valid_css_color("not-a-color"));
+    }
+
+    #[test]
+    fn test_rejects_style_injection() {
+        assert!(!is_valid_css_color("red; background:url(javascript:alert(1))"));
+        assert!(!is_valid_css_color("expression(alert(1))"));
+    }
+
+    #[test]
+    fn test_render_literal_hex_color() {
+        let html = render("#ff0000", "red text");
+        assert_eq!(html, "<span style=\"color: #ff0000\">red text</span>");
+    }
+
+    #[test]
+    fn test_render_literal_rgb_color() {
+        let html = render("rgb(255, 0, 0)", "red text");
+        assert_eq!(html, "<span style=\"color: rgb(255, 0, 0)\">red text</span>");
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_literal() {
+        let html = render("red; background:url(x)", "text");
+        assert_eq!(html, "COLOR(red; background:url(x)):text");
+    }
+
+    #[test]
+    fn test_unknown_keyword_falls_back_to_literal() {
+        let html = render("not-a-keyword", " text");
+        assert_eq!(html, "COLOR(not-a-keyword): text");
+    }
+
+    #[test]
+    fn test_render_escapes_unsanitized_text() {
+        let html = render("red", "<img src=x onerror=alert(1)>");
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;img"));
+    }
+
+    #[test]
+    fn test_render_semantic_escapes_unsanitized_text() {
+        let html = render("success", "<script>alert(1)</script>");
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+}
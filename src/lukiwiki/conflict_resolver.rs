@@ -0,0 +1,379 @@
+//! Conflict resolution between LukiWiki plugin syntax and the downstream
+//! sanitizer / CommonMark passes.
+//!
+//! `&name(args){content};`, `@name(args){{content}}`, `COLOR(value): ...`
+//! and `:::spoiler ... :::` fenced blocks all use characters (`&`, raw
+//! braces, a line-initial `:::`) that [`crate::sanitizer`] or comrak would
+//! otherwise escape or reinterpret. `preprocess_conflicts` recognizes these
+//! constructs before sanitization and replaces each with an opaque
+//! placeholder token (hex-encoded, wrapped in control characters) that
+//! survives both passes unchanged. [`crate::lukiwiki::apply_lukiwiki_syntax`]
+//! restores and renders the original syntax once the surrounding HTML has
+//! been produced.
+
+/// Start-of-token marker (ASCII STX). Chosen because it cannot appear in
+/// normal wiki text and is left untouched by both the sanitizer and comrak.
+pub(crate) const TOKEN_START: char = '\u{2}';
+/// End-of-token marker (ASCII ETX).
+pub(crate) const TOKEN_END: char = '\u{3}';
+
+/// Placeholder token kind, encoded as the first byte of its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    InlinePlugin,
+    BlockPlugin,
+    Color,
+    Spoiler,
+}
+
+impl TokenKind {
+    fn tag(self) -> char {
+        match self {
+            TokenKind::InlinePlugin => 'I',
+            TokenKind::BlockPlugin => 'B',
+            TokenKind::Color => 'C',
+            TokenKind::Spoiler => 'S',
+        }
+    }
+
+    fn from_tag(tag: char) -> Option<Self> {
+        match tag {
+            'I' => Some(TokenKind::InlinePlugin),
+            'B' => Some(TokenKind::BlockPlugin),
+            'C' => Some(TokenKind::Color),
+            'S' => Some(TokenKind::Spoiler),
+            _ => None,
+        }
+    }
+}
+
+/// Replace recognized LukiWiki syntax with opaque placeholder tokens so it
+/// survives sanitization and CommonMark parsing unchanged.
+pub fn preprocess_conflicts(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+
+        if ch == '@' {
+            if let Some((raw, next)) = scan_block_plugin(&chars, i) {
+                out.push_str(&encode_token(TokenKind::BlockPlugin, &raw));
+                i = next;
+                continue;
+            }
+        } else if ch == '&' {
+            if let Some((raw, next)) = scan_inline_plugin(&chars, i) {
+                // A bare `&name;` that the sanitizer keeps as a character
+                // reference (e.g. `&nbsp;`) is an entity, not a plugin call.
+                let is_entity =
+                    !raw.contains(['(', '{']) && crate::sanitizer::sanitize(&raw) == raw;
+                if !is_entity {
+                    out.push_str(&encode_token(TokenKind::InlinePlugin, &raw));
+                    i = next;
+                    continue;
+                }
+            }
+        } else if starts_with(&chars, i, "COLOR(") {
+            if let Some((raw, next)) = scan_color(&chars, i) {
+                out.push_str(&encode_token(TokenKind::Color, &raw));
+                i = next;
+                continue;
+            }
+        } else if (i == 0 || chars[i - 1] == '\n') && starts_with(&chars, i, ":::spoiler") {
+            if let Some((raw, next)) = scan_spoiler(&chars, i) {
+                out.push_str(&encode_token(TokenKind::Spoiler, &raw));
+                i = next;
+                continue;
+            }
+        }
+
+        out.push(ch);
+        i += 1;
+    }
+
+    out
+}
+
+fn starts_with(chars: &[char], at: usize, needle: &str) -> bool {
+    let needle: Vec<char> = needle.chars().collect();
+    at + needle.len() <= chars.len() && chars[at..at + needle.len()] == needle[..]
+}
+
+/// Scan `@name(args)` optionally followed by `{{content}}`, starting at `@`.
+/// Returns the raw matched text and the index just past it.
+fn scan_block_plugin(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    let name_start = i;
+    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+        i += 1;
+    }
+    if i == name_start || i >= chars.len() || chars[i] != '(' {
+        return None; // no identifier, or `@mention` without parens.
+    }
+
+    while i < chars.len() && chars[i] != ')' {
+        i += 1;
+    }
+    if i >= chars.len() {
+        return None;
+    }
+    let paren_end = i;
+    i += 1;
+
+    if i + 1 < chars.len() && chars[i] == '{' && chars[i + 1] == '{' {
+        let body_start = i + 2;
+        let close = find_seq(chars, body_start, &['}', '}'])?;
+        let end = close + 2;
+        return Some((chars[start..end].iter().collect(), end));
+    }
+
+    Some((chars[start..=paren_end].iter().collect(), paren_end + 1))
+}
+
+/// Scan `&name`, optionally `(args)`, optionally `{content}`, then `;`.
+fn scan_inline_plugin(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    let name_start = i;
+    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+        i += 1;
+    }
+    if i == name_start {
+        return None;
+    }
+
+    if i < chars.len() && chars[i] == '(' {
+        i += 1;
+        while i < chars.len() && chars[i] != ')' {
+            i += 1;
+        }
+        if i >= chars.len() {
+            return None;
+        }
+        i += 1;
+    }
+
+    if i < chars.len() && chars[i] == '{' {
+        i += 1;
+        while i < chars.len() && chars[i] != '}' {
+            i += 1;
+        }
+        if i >= chars.len() {
+            return None;
+        }
+        i += 1;
+    }
+
+    if i >= chars.len() || chars[i] != ';' {
+        return None;
+    }
+    i += 1;
+
+    Some((chars[start..i].iter().collect(), i))
+}
+
+/// Scan `COLOR(value):` plus the rest of the current line. `value` may
+/// itself contain parentheses (e.g. `rgb(255, 0, 0)`), so the closing `)`
+/// is found by tracking paren depth rather than the first `)`.
+fn scan_color(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut i = start + "COLOR(".chars().count();
+    let value_start = i;
+    let mut depth = 1;
+
+    while i < chars.len() && depth > 0 {
+        match chars[i] {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if depth != 0 || i == value_start {
+        return None;
+    }
+    i += 1; // past the matching ')'
+
+    if i >= chars.len() || chars[i] != ':' {
+        return None;
+    }
+    i += 1; // past ':'
+
+    while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+    }
+
+    Some((chars[start..i].iter().collect(), i))
+}
+
+/// Scan a `:::spoiler Optional summary` ... `:::` fenced block, starting at
+/// the first `:`. Nested fences (another line-initial `:::word`) are
+/// tracked by depth so they're captured as part of the body rather than
+/// closing the outer block early. Returns `summary\u{1}body` (the raw
+/// source text isn't otherwise needed, so the separator-joined pair is
+/// stored directly) and the index just past the closing `:::` line.
+fn scan_spoiler(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let i = start + ":::spoiler".chars().count();
+    let line_end = (i..chars.len()).find(|&j| chars[j] == '\n').unwrap_or(chars.len());
+    let summary: String = chars[i..line_end].iter().collect();
+    let summary = summary.trim().to_string();
+
+    let body_start = if line_end < chars.len() { line_end + 1 } else { line_end };
+
+    let mut depth = 1usize;
+    let mut pos = body_start;
+    loop {
+        let line_end = (pos..chars.len()).find(|&j| chars[j] == '\n').unwrap_or(chars.len());
+        let line: String = chars[pos..line_end].iter().collect();
+        let trimmed = line.trim();
+
+        if trimmed == ":::" {
+            depth -= 1;
+            if depth == 0 {
+                let mut body: String = chars[body_start..pos].iter().collect();
+                if body.ends_with('\n') {
+                    body.pop();
+                }
+                let next = if line_end < chars.len() { line_end + 1 } else { line_end };
+                return Some((format!("{summary}\u{1}{body}"), next));
+            }
+        } else if trimmed.starts_with(":::") && trimmed.len() > 3 {
+            depth += 1;
+        }
+
+        if line_end >= chars.len() {
+            return None; // unterminated block
+        }
+        pos = line_end + 1;
+    }
+}
+
+fn find_seq(chars: &[char], from: usize, seq: &[char]) -> Option<usize> {
+    if seq.is_empty() || from + seq.len() > chars.len() {
+        return None;
+    }
+    (from..=chars.len() - seq.len()).find(|&i| chars[i..i + seq.len()] == *seq)
+}
+
+fn encode_token(kind: TokenKind, raw: &str) -> String {
+    let mut hex = String::with_capacity(raw.len() * 2 + 1);
+    hex.push(kind.tag());
+    for byte in raw.as_bytes() {
+        hex.push_str(&format!("{byte:02x}"));
+    }
+    format!("{TOKEN_START}{hex}{TOKEN_END}")
+}
+
+/// Decode a placeholder payload (the text between [`TOKEN_START`] and
+/// [`TOKEN_END`], tag included) back into its kind and original text.
+pub(crate) fn decode_token(payload: &str) -> Option<(TokenKind, String)> {
+    let mut chars = payload.chars();
+    let kind = TokenKind::from_tag(chars.next()?)?;
+    let hex: String = chars.collect();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(hex.len() / 2);
+    for chunk in hex.as_bytes().chunks(2) {
+        bytes.push(u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?);
+    }
+    Some((kind, String::from_utf8(bytes).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protects_inline_plugin() {
+        let out = preprocess_conflicts("&highlight(yellow){important text};");
+        assert!(!out.contains('&'));
+        assert!(out.starts_with(TOKEN_START));
+    }
+
+    #[test]
+    fn test_protects_block_plugin_with_content() {
+        let out = preprocess_conflicts("@code(rust){{ fn main() {} }}");
+        assert!(!out.contains('@'));
+    }
+
+    #[test]
+    fn test_self_closing_block_plugin() {
+        let out = preprocess_conflicts("@toc()");
+        assert!(!out.contains('@'));
+    }
+
+    #[test]
+    fn test_mention_without_parens_is_untouched() {
+        let input = "This is @mention without parens";
+        assert_eq!(preprocess_conflicts(input), input);
+    }
+
+    #[test]
+    fn test_roundtrip_token() {
+        let token = encode_token(TokenKind::InlinePlugin, "&br;");
+        let payload = token.trim_matches(|c| c == TOKEN_START || c == TOKEN_END);
+        let (kind, decoded) = decode_token(payload).unwrap();
+        assert_eq!(kind, TokenKind::InlinePlugin);
+        assert_eq!(decoded, "&br;");
+    }
+
+    #[test]
+    fn test_color_syntax_is_protected() {
+        let out = preprocess_conflicts("COLOR(success): This is a success message");
+        assert!(!out.contains("COLOR("));
+    }
+
+    #[test]
+    fn test_color_syntax_with_nested_parens_is_protected() {
+        let out = preprocess_conflicts("COLOR(rgb(255, 0, 0)): red text");
+        assert!(!out.contains("COLOR("));
+        let (kind, raw) = decode_token(
+            out.trim_matches(|c| c == TOKEN_START || c == TOKEN_END),
+        )
+        .unwrap();
+        assert_eq!(kind, TokenKind::Color);
+        assert_eq!(raw, "COLOR(rgb(255, 0, 0)): red text");
+    }
+
+    #[test]
+    fn test_spoiler_block_is_protected() {
+        let out = preprocess_conflicts(":::spoiler Click to reveal\nsecret text\n:::");
+        assert!(!out.contains(":::"));
+        let (kind, raw) =
+            decode_token(out.trim_matches(|c| c == TOKEN_START || c == TOKEN_END)).unwrap();
+        assert_eq!(kind, TokenKind::Spoiler);
+        assert_eq!(raw, "Click to reveal\u{1}secret text");
+    }
+
+    #[test]
+    fn test_spoiler_block_without_summary() {
+        let out = preprocess_conflicts(":::spoiler\nbody\n:::");
+        let (_, raw) =
+            decode_token(out.trim_matches(|c| c == TOKEN_START || c == TOKEN_END)).unwrap();
+        assert_eq!(raw, "\u{1}body");
+    }
+
+    #[test]
+    fn test_nested_spoiler_blocks() {
+        let input = ":::spoiler Outer\nbefore\n:::spoiler Inner\nnested\n:::\nafter\n:::";
+        let out = preprocess_conflicts(input);
+        let (_, raw) =
+            decode_token(out.trim_matches(|c| c == TOKEN_START || c == TOKEN_END)).unwrap();
+        assert_eq!(
+            raw,
+            "Outer\u{1}before\n:::spoiler Inner\nnested\n:::\nafter"
+        );
+    }
+
+    #[test]
+    fn test_unterminated_spoiler_is_untouched() {
+        let input = ":::spoiler Oops\nno closing fence";
+        assert_eq!(preprocess_conflicts(input), input);
+    }
+}
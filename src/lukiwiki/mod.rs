@@ -0,0 +1,188 @@
+//! LukiWiki-specific syntax transforms
+//!
+//! Applied after the CommonMark pass (see [`crate::parser`]) to turn the
+//! placeholder tokens left by [`conflict_resolver::preprocess_conflicts`]
+//! back into their final HTML representation.
+
+pub mod conflict_resolver;
+mod color;
+mod plugins;
+mod toc;
+
+use crate::parser::{Heading, ParserOptions};
+use conflict_resolver::{decode_token, TokenKind, TOKEN_END, TOKEN_START};
+
+/// Expand every placeholder token in `html` into its final HTML.
+///
+/// `headings` is the list collected while generating heading anchor IDs
+/// (see [`crate::parser::parse_to_html_with_headings`]); it's used to
+/// render `@toc()` blocks. `options` is threaded through to
+/// [`render_nested_markup`] so nested content (e.g. a spoiler body) is
+/// parsed with the same [`ParserOptions`] as the surrounding document.
+pub fn apply_lukiwiki_syntax(html: &str, headings: &[Heading], options: &ParserOptions) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find(TOKEN_START) {
+        out.push_str(&rest[..start]);
+        let after_start = &rest[start + TOKEN_START.len_utf8()..];
+        let Some(end) = after_start.find(TOKEN_END) else {
+            // Unterminated token: shouldn't happen, emit verbatim.
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let payload = &after_start[..end];
+        let rendered = decode_token(payload)
+            .map(|(kind, raw)| render_token(kind, &raw, headings, options))
+            .unwrap_or_else(|| payload.to_string());
+        out.push_str(&rendered);
+        rest = &after_start[end + TOKEN_END.len_utf8()..];
+    }
+    out.push_str(rest);
+
+    unwrap_block_plugin_paragraphs(&out)
+}
+
+fn render_token(kind: TokenKind, raw: &str, headings: &[Heading], options: &ParserOptions) -> String {
+    match kind {
+        TokenKind::InlinePlugin => {
+            // raw: "&name(args){content};"
+            let body = raw.trim_start_matches('&').trim_end_matches(';');
+            plugins::render("span", &plugins::parse_inline(body))
+        }
+        TokenKind::BlockPlugin => {
+            // raw: "@name(args){{content}}" or "@name(args)"
+            let body = raw.trim_start_matches('@');
+            let call = plugins::parse_block(body);
+            if call.name == "toc" {
+                toc::render_toc(headings)
+            } else {
+                plugins::render("div", &call)
+            }
+        }
+        TokenKind::Color => {
+            // raw: "COLOR(value): text"
+            let rest = raw.trim_start_matches("COLOR(");
+            let (value, text) = rest.split_once("):").unwrap_or((rest, ""));
+            color::render(value, text.trim_start())
+        }
+        TokenKind::Spoiler => {
+            // raw: "summary\u{1}body" (body is the fenced block's raw content)
+            let (summary, body) = raw.split_once('\u{1}').unwrap_or((raw, ""));
+            let summary = if summary.is_empty() { "Spoiler" } else { summary };
+            let summary_html = crate::sanitizer::sanitize(summary);
+            let body_html = render_nested_markup(body, options);
+            format!("<details><summary>{summary_html}</summary>\n\n{body_html}\n</details>")
+        }
+    }
+}
+
+/// Run `body` (a spoiler block's inner source) back through the full
+/// conflict-protection/sanitize/parse/apply pipeline, using the same
+/// `options` as the surrounding document, so nested plugins, COLOR syntax,
+/// and nested spoilers all render the same as top-level content.
+fn render_nested_markup(body: &str, options: &ParserOptions) -> String {
+    let preprocessed = conflict_resolver::preprocess_conflicts(body);
+    let sanitized = crate::sanitizer::sanitize_with(&preprocessed, options.html_mode);
+    let (html, headings) = crate::parser::parse_to_html_with_headings(&sanitized, options);
+    apply_lukiwiki_syntax(&html, &headings, options)
+}
+
+/// comrak wraps a standalone block plugin token in `<p>...</p>` since it
+/// looked like an ordinary paragraph; strip that wrapper for block-level
+/// plugin output, since it isn't inline content.
+fn unwrap_block_plugin_paragraphs(html: &str) -> String {
+    html.replace("<p><div", "<div").replace("</div></p>", "</div>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParserOptions;
+
+    #[test]
+    fn test_inline_plugin_roundtrip() {
+        let preprocessed =
+            conflict_resolver::preprocess_conflicts("&highlight(yellow){important text};");
+        let options = ParserOptions::default();
+        let html = crate::parser::parse_to_html(&preprocessed, &options);
+        let final_html = apply_lukiwiki_syntax(&html, &[], &options);
+        assert!(final_html.contains("data-args='[\"yellow\"]'"));
+        assert!(final_html.contains("important text"));
+    }
+
+    #[test]
+    fn test_toc_plugin_renders_headings() {
+        let preprocessed = conflict_resolver::preprocess_conflicts("# Hello World\n\n@toc()");
+        let options = ParserOptions::default();
+        let (html, headings) = crate::parser::parse_to_html_with_headings(&preprocessed, &options);
+        let final_html = apply_lukiwiki_syntax(&html, &headings, &options);
+        assert!(final_html.contains("plugin-toc"));
+        assert!(final_html.contains("href=\"#hello-world\""));
+    }
+
+    fn render_document(input: &str) -> String {
+        render_document_with_options(input, &ParserOptions::default())
+    }
+
+    fn render_document_with_options(input: &str, options: &ParserOptions) -> String {
+        let preprocessed = conflict_resolver::preprocess_conflicts(input);
+        let (html, headings) = crate::parser::parse_to_html_with_headings(&preprocessed, options);
+        apply_lukiwiki_syntax(&html, &headings, options)
+    }
+
+    #[test]
+    fn test_spoiler_renders_details_with_summary() {
+        let html = render_document(":::spoiler Plot twist\nIt was a dream.\n:::");
+        assert!(html.contains("<summary>Plot twist</summary>"));
+        assert!(html.contains("It was a dream."));
+        assert!(html.contains("<details>"));
+        assert!(html.contains("</details>"));
+    }
+
+    #[test]
+    fn test_spoiler_defaults_summary_when_missing() {
+        let html = render_document(":::spoiler\nhidden\n:::");
+        assert!(html.contains("<summary>Spoiler</summary>"));
+    }
+
+    #[test]
+    fn test_spoiler_body_parsed_as_block_markup() {
+        let html = render_document(":::spoiler List\n- one\n- two\n:::");
+        assert!(html.contains("<ul>"));
+        assert!(html.contains("<li>one</li>"));
+    }
+
+    #[test]
+    fn test_nested_spoilers_both_render() {
+        let html = render_document(
+            ":::spoiler Outer\nbefore\n:::spoiler Inner\nnested\n:::\nafter\n:::",
+        );
+        assert!(html.contains("<summary>Outer</summary>"));
+        assert!(html.contains("<summary>Inner</summary>"));
+        assert!(html.contains("nested"));
+        assert_eq!(html.matches("<details>").count(), 2);
+    }
+
+    #[test]
+    fn test_spoiler_summary_is_escaped() {
+        let html = render_document(":::spoiler <script>alert(1)</script>\nbody\n:::");
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_spoiler_body_inherits_caller_html_mode() {
+        let options = ParserOptions {
+            html_mode: crate::sanitizer::HtmlMode::Allowlist,
+            ..ParserOptions::default()
+        };
+        let html = render_document_with_options(
+            "<b>top-level bold</b>\n\n:::spoiler\n<b>nested bold</b>\n:::",
+            &options,
+        );
+        assert!(html.contains("<b>top-level bold</b>"));
+        assert!(html.contains("<b>nested bold</b>"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Generic LukiWiki plugin rendering
+//!
+//! Turns a parsed `&name(args){content};` / `@name(args){{content}}`
+//! invocation into the markup a host application's plugin runtime looks
+//! for: a `<span>`/`<div>` carrying the plugin name as a CSS class and its
+//! arguments as a `data-args` JSON array. Plugin *execution* (actually
+//! fetching a feed, rendering an icon, ...) happens outside this crate.
+
+/// A parsed plugin invocation.
+pub(crate) struct PluginCall {
+    pub name: String,
+    pub args: Vec<String>,
+    pub content: Option<String>,
+}
+
+/// Parse `name(args){content}` / `name(args)` / `name` (the leading `&`/`@`
+/// and, for inline plugins, the trailing `;` must already be stripped).
+pub(crate) fn parse_inline(raw: &str) -> PluginCall {
+    let (head, content) = match raw.find('{') {
+        Some(idx) => {
+            let close = raw.rfind('}').unwrap_or(raw.len());
+            (&raw[..idx], Some(raw[idx + 1..close].to_string()))
+        }
+        None => (raw, None),
+    };
+    let (name, args) = split_name_args(head);
+    PluginCall { name, args, content }
+}
+
+/// Parse `name(args){{content}}` / `name(args)`.
+pub(crate) fn parse_block(raw: &str) -> PluginCall {
+    let (head, content) = match raw.find("{{") {
+        Some(idx) => {
+            let close = raw.rfind("}}").unwrap_or(raw.len());
+            (&raw[..idx], Some(raw[idx + 2..close].to_string()))
+        }
+        None => (raw, None),
+    };
+    let (name, args) = split_name_args(head);
+    PluginCall { name, args, content }
+}
+
+fn split_name_args(head: &str) -> (String, Vec<String>) {
+    match head.find('(') {
+        Some(idx) => {
+            let name = head[..idx].to_string();
+            let close = head.rfind(')').unwrap_or(head.len());
+            let inner = &head[idx + 1..close];
+            let args = if inner.trim().is_empty() {
+                Vec::new()
+            } else {
+                inner.split(',').map(|a| a.trim().to_string()).collect()
+            };
+            (name, args)
+        }
+        None => (head.to_string(), Vec::new()),
+    }
+}
+
+/// Render a plugin call to its final HTML element.
+///
+/// `tag` is `"span"` for inline plugins and `"div"` for block plugins.
+/// `content` is raw wikitext captured before sanitization (see
+/// [`crate::lukiwiki::conflict_resolver`]) so it's escaped here before
+/// being embedded — plugin *output* is trusted, but the literal text an
+/// author typed between `{...}` is not.
+pub(crate) fn render(tag: &str, call: &PluginCall) -> String {
+    let args_json = format!(
+        "[{}]",
+        call.args
+            .iter()
+            .map(|a| format!("\"{}\"", json_escape(a)))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let class = format!("plugin-{}", call.name);
+
+    match &call.content {
+        Some(content) => {
+            let content = crate::sanitizer::sanitize(content);
+            format!("<{tag} class=\"{class}\" data-args='{args_json}'>{content}</{tag}>")
+        }
+        None => format!("<{tag} class=\"{class}\" data-args='{args_json}' />"),
+    }
+}
+
+/// Escape `s` as a JSON string body for the single-quoted `data-args`
+/// attribute. `&`, `'`, `<` and `>` become `\uXXXX` escapes, so author-typed
+/// arguments can't close the attribute or the tag.
+fn json_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('&', "\\u0026")
+        .replace('\'', "\\u0027")
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inline_with_args_and_content() {
+        let call = parse_inline("highlight(yellow){important text}");
+        assert_eq!(call.name, "highlight");
+        assert_eq!(call.args, vec!["yellow"]);
+        assert_eq!(call.content.as_deref(), Some("important text"));
+    }
+
+    #[test]
+    fn test_parse_inline_no_args_no_content() {
+        let call = parse_inline("br");
+        assert_eq!(call.name, "br");
+        assert!(call.args.is_empty());
+        assert!(call.content.is_none());
+    }
+
+    #[test]
+    fn test_parse_block_with_nested_content() {
+        let call = parse_block("code(rust){{ fn main() {} }}");
+        assert_eq!(call.name, "code");
+        assert_eq!(call.args, vec!["rust"]);
+        assert_eq!(call.content.as_deref(), Some(" fn main() {} "));
+    }
+
+    #[test]
+    fn test_render_escapes_unsanitized_content() {
+        let call = PluginCall {
+            name: "foo".to_string(),
+            args: vec!["bar".to_string()],
+            content: Some("<script>alert(1)</script>".to_string()),
+        };
+        let html = render("span", &call);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_render_self_closing() {
+        let call = PluginCall {
+            name: "br".to_string(),
+            args: Vec::new(),
+            content: None,
+        };
+        let html = render("span", &call);
+        assert_eq!(
+            html,
+            "<span class=\"plugin-br\" data-args='[]' />"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! `@toc()` table-of-contents rendering
+
+use crate::parser::Heading;
+
+/// Render a nested `<ul>` of in-page anchor links for `headings`, wrapped
+/// the same way [`super::plugins::render`] would wrap any other block
+/// plugin, so `@toc()` composes with the rest of the plugin pipeline.
+pub(crate) fn render_toc(headings: &[Heading]) -> String {
+    // A heading shallower than the ones before it starts a new top-level list.
+    let mut list = String::new();
+    let mut idx = 0;
+    while idx < headings.len() {
+        let (nested, next_idx) = render_nested(headings, idx, headings[idx].level);
+        list.push_str(&nested);
+        idx = next_idx;
+    }
+    format!("<div class=\"plugin-toc\" data-args='[]'>{list}</div>")
+}
+
+/// Render the `<ul>` for every heading at `level` starting at `start`,
+/// recursing into a nested `<ul>` for deeper headings. Returns the
+/// rendered markup and the index of the first heading not consumed.
+fn render_nested(headings: &[Heading], start: usize, level: u8) -> (String, usize) {
+    let mut idx = start;
+    let mut out = String::from("<ul>");
+
+    while idx < headings.len() && headings[idx].level >= level {
+        if headings[idx].level > level {
+            break; // handled by the caller's own recursive call
+        }
+
+        let heading = &headings[idx];
+        out.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            heading.id, heading.text
+        ));
+        idx += 1;
+
+        while idx < headings.len() && headings[idx].level > level {
+            let (nested, next_idx) = render_nested(headings, idx, headings[idx].level);
+            out.push_str(&nested);
+            idx = next_idx;
+        }
+        out.push_str("</li>");
+    }
+
+    out.push_str("</ul>");
+    (out, idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h(level: u8, id: &str, text: &str) -> Heading {
+        Heading {
+            level,
+            id: id.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_empty_toc() {
+        let out = render_toc(&[]);
+        assert_eq!(out, "<div class=\"plugin-toc\" data-args='[]'></div>");
+    }
+
+    #[test]
+    fn test_flat_toc() {
+        let headings = vec![h(2, "a", "A"), h(2, "b", "B")];
+        let out = render_toc(&headings);
+        assert_eq!(
+            out,
+            "<div class=\"plugin-toc\" data-args='[]'><ul><li><a href=\"#a\">A</a></li><li><a href=\"#b\">B</a></li></ul></div>"
+        );
+    }
+
+    #[test]
+    fn test_nested_toc() {
+        let headings = vec![h(1, "intro", "Intro"), h(2, "sub", "Sub")];
+        let out = render_toc(&headings);
+        assert!(out.contains("<li><a href=\"#intro\">Intro</a><ul><li><a href=\"#sub\">Sub</a></li></ul></li>"));
+    }
+}
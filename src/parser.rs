@@ -0,0 +1,471 @@
+//! Markdown-to-HTML parsing
+//!
+//! Wraps a CommonMark-compliant parser (comrak) and applies LukiWiki's own
+//! post-processing passes, such as heading anchor generation.
+
+use crate::sanitizer::HtmlMode;
+use comrak::{markdown_to_html, ComrakOptions};
+use std::collections::{HashMap, HashSet};
+
+/// A single heading discovered while assigning anchor IDs.
+///
+/// Collected so callers (e.g. the `@toc()` plugin, or a host application
+/// building a sidebar) don't have to re-scan the rendered HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Heading level, 1 (`<h1>`) through 6 (`<h6>`).
+    pub level: u8,
+    /// Slug used as the heading's `id` attribute.
+    pub id: String,
+    /// Heading text with inline HTML tags stripped.
+    pub text: String,
+}
+
+/// Options controlling how LukiWiki markup is converted to HTML.
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    /// Assign a stable `id` attribute to every heading so it can be linked
+    /// to and so `@toc()` can enumerate them. Enabled by default.
+    pub generate_heading_ids: bool,
+    /// How raw HTML in the input is sanitized before parsing. Defaults to
+    /// [`HtmlMode::EscapeAll`]; set to [`HtmlMode::Allowlist`] to let a
+    /// fixed set of inline HTML tags pass through.
+    pub html_mode: HtmlMode,
+    /// Base URL of an image proxy. When set, every remote `<img src="...">`
+    /// in the rendered output is rewritten to route through
+    /// `{image_proxy}?url={percent_encoded_original}`, mitigating IP-leak
+    /// and mixed-content issues when rendering federated or user content.
+    /// Relative and already-proxied URLs are left untouched. `None` (the
+    /// default) disables rewriting.
+    pub image_proxy: Option<String>,
+    /// Typography profile applied to the rendered output. `None` (the
+    /// default) leaves punctuation untouched.
+    pub typography: crate::cleaner::Typography,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            generate_heading_ids: true,
+            html_mode: HtmlMode::default(),
+            image_proxy: None,
+            typography: crate::cleaner::Typography::default(),
+        }
+    }
+}
+
+/// Convert LukiWiki/CommonMark source to HTML.
+///
+/// Equivalent to [`parse_to_html_with_headings`] for callers that don't
+/// need the collected heading list.
+pub fn parse_to_html(input: &str, options: &ParserOptions) -> String {
+    parse_to_html_with_headings(input, options).0
+}
+
+/// Convert LukiWiki/CommonMark source to HTML, also returning the headings
+/// discovered while generating anchor IDs.
+///
+/// When `options.generate_heading_ids` is set, every `<h1>`-`<h6>` produced
+/// by the CommonMark pass is assigned a slug `id` (see [`slugify`]);
+/// collisions with an earlier heading are disambiguated with a `-1`, `-2`,
+/// ... suffix. The heading list is empty when the option is disabled.
+pub fn parse_to_html_with_headings(
+    input: &str,
+    options: &ParserOptions,
+) -> (String, Vec<Heading>) {
+    let mut comrak_options = ComrakOptions::default();
+    comrak_options.extension.table = true;
+    comrak_options.extension.strikethrough = true;
+    comrak_options.extension.autolink = true;
+    // `sanitizer::sanitize_with` already dropped everything but the fixed
+    // allowlist when `html_mode` is `Allowlist`; comrak's own raw-HTML
+    // stripping (the `unsafe_` default) would otherwise replace those
+    // surviving tags with an HTML comment before they ever reach the
+    // output.
+    comrak_options.render.unsafe_ = options.html_mode == HtmlMode::Allowlist;
+
+    let html = markdown_to_html(input, &comrak_options);
+
+    if options.generate_heading_ids {
+        assign_heading_ids(&html)
+    } else {
+        (html, Vec::new())
+    }
+}
+
+/// Scan `html` for `<h1>`-`<h6>` tags, drop any `id` comrak already set, and
+/// assign a fresh slug based on each heading's text content.
+fn assign_heading_ids(html: &str) -> (String, Vec<Heading>) {
+    let mut out = String::with_capacity(html.len() + 64);
+    let mut headings = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut used_ids: HashSet<String> = HashSet::new();
+
+    let mut rest = html;
+    while let Some(start) = rest.find("<h") {
+        let level = rest[start + 2..]
+            .chars()
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .filter(|l| (1..=6).contains(l));
+
+        let Some(level) = level else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        };
+        let level = level as u8;
+
+        let Some(open_tag_end) = rest[start..].find('>').map(|i| start + i + 1) else {
+            out.push_str(rest);
+            rest = "";
+            break;
+        };
+
+        let close_tag = format!("</h{level}>");
+        let Some(close_start) = rest[open_tag_end..].find(&close_tag).map(|i| open_tag_end + i)
+        else {
+            out.push_str(&rest[..open_tag_end]);
+            rest = &rest[open_tag_end..];
+            continue;
+        };
+
+        out.push_str(&rest[..start]);
+
+        let inner = &rest[open_tag_end..close_start];
+        let text = strip_tags(inner);
+        let mut slug = slugify(&text);
+        if slug.is_empty() {
+            slug = "section".to_string();
+        }
+
+        let count = seen.entry(slug.clone()).or_insert(0);
+        let mut id = if *count == 0 {
+            slug.clone()
+        } else {
+            format!("{slug}-{count}")
+        };
+        *count += 1;
+        // A suffixed id (`intro-1`) can also be another heading's plain slug.
+        while !used_ids.insert(id.clone()) {
+            id = format!("{slug}-{count}");
+            *count += 1;
+        }
+
+        out.push_str(&format!("<h{level} id=\"{id}\">"));
+        out.push_str(inner);
+        out.push_str(&close_tag);
+
+        headings.push(Heading { level, id, text });
+        rest = &rest[close_start + close_tag.len()..];
+    }
+    out.push_str(rest);
+
+    (out, headings)
+}
+
+/// Strip HTML tags from `input`, leaving only text content.
+fn strip_tags(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut in_tag = false;
+    for ch in input.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(ch),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Rewrite every remote (`http`/`https`) `<img src="...">` in `html` to
+/// route through `proxy_base`, as `{proxy_base}?url={percent_encoded_original}`.
+/// Relative URLs and URLs already under `proxy_base` are left untouched.
+///
+/// Returns the rewritten HTML and the list of original URLs that were
+/// rewritten, in document order, so a host application can prefetch or
+/// validate them.
+pub fn rewrite_image_proxies(html: &str, proxy_base: &str) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(html.len());
+    let mut rewritten = Vec::new();
+    let mut rest = html;
+
+    while let Some(tag_start) = find_img_tag(rest) {
+        out.push_str(&rest[..tag_start]);
+        let after = &rest[tag_start..];
+        let tag_end = after.find('>').map_or(after.len(), |i| i + 1);
+        out.push_str(&rewrite_img_src(&after[..tag_end], proxy_base, &mut rewritten));
+        rest = &after[tag_end..];
+    }
+    out.push_str(rest);
+
+    (out, rewritten)
+}
+
+/// Find the next `<img` tag start in `html`, rejecting look-alikes such as
+/// `<imgur>`.
+fn find_img_tag(html: &str) -> Option<usize> {
+    let mut search_from = 0;
+    while let Some(idx) = html[search_from..].find("<img") {
+        let pos = search_from + idx;
+        let boundary = html[pos + 4..].chars().next();
+        if boundary.is_none_or(|c| c == '>' || c == '/' || c.is_whitespace()) {
+            return Some(pos);
+        }
+        search_from = pos + 4;
+    }
+    None
+}
+
+/// Rewrite the `src` attribute of a single `<img ...>` tag if its URL is
+/// remote, recording the original URL in `rewritten`.
+fn rewrite_img_src(tag: &str, proxy_base: &str, rewritten: &mut Vec<String>) -> String {
+    let Some((value_start, value_end)) = find_src_value_span(tag) else {
+        return tag.to_string();
+    };
+    let url = &tag[value_start..value_end];
+
+    if !is_remote_url(url) || url.starts_with(proxy_base) {
+        return tag.to_string();
+    }
+
+    rewritten.push(url.to_string());
+    format!(
+        "{}{}?url={}{}",
+        &tag[..value_start],
+        proxy_base,
+        percent_encode(url),
+        &tag[value_end..]
+    )
+}
+
+/// Find the byte span of the `src` attribute's value (excluding quotes) in
+/// `tag`, by walking its attributes rather than substring-searching for
+/// `"src="` — a plain substring search would match inside an earlier
+/// attribute's value (e.g. `alt="src="`) and go off the rails from there.
+///
+/// Byte-indexing into `tag` is safe here: every byte we compare against
+/// (`=`, quotes, ASCII whitespace, `>`) is single-byte ASCII, and UTF-8
+/// continuation bytes never equal them, so every returned index still
+/// falls on a `char` boundary.
+fn find_src_value_span(tag: &str) -> Option<(usize, usize)> {
+    let bytes = tag.as_bytes();
+    let mut i = 0;
+
+    // Skip the leading `<img` tag name.
+    while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+
+    while i < bytes.len() {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] == b'>' {
+            break;
+        }
+        let name_start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'-' | b'_'))
+        {
+            i += 1;
+        }
+        if i == name_start {
+            i += 1; // skip a stray character (e.g. a lone '/')
+            continue;
+        }
+        let name = &tag[name_start..i];
+
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        let mut value_span = None;
+        if i < bytes.len() && bytes[i] == b'=' {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i < bytes.len() && matches!(bytes[i], b'"' | b'\'') {
+                let quote = bytes[i];
+                i += 1;
+                let value_start = i;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                value_span = Some((value_start, i));
+                if i < bytes.len() {
+                    i += 1; // past closing quote
+                }
+            } else {
+                let value_start = i;
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                    i += 1;
+                }
+                value_span = Some((value_start, i));
+            }
+        }
+
+        if name.eq_ignore_ascii_case("src") {
+            return value_span;
+        }
+    }
+
+    None
+}
+
+fn is_remote_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Percent-encode `input` for use as a URL query parameter value, keeping
+/// only the RFC 3986 "unreserved" characters unescaped.
+fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len() * 3);
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char);
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Derive a URL-safe slug from heading text.
+///
+/// Keeps `[A-Za-z0-9_-]` (lowercased), collapses runs of whitespace into a
+/// single `-`, and drops everything else.
+pub fn slugify(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pending_dash = false;
+
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            pending_dash = !out.is_empty();
+            continue;
+        }
+        if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+            if pending_dash {
+                out.push('-');
+                pending_dash = false;
+            }
+            out.extend(ch.to_lowercase());
+        }
+        // Everything else (punctuation, symbols, non-ASCII letters) is dropped.
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify_basic() {
+        assert_eq!(slugify("Hello World"), "hello-world");
+    }
+
+    #[test]
+    fn test_slugify_strips_punctuation() {
+        assert_eq!(slugify("What's New?!"), "whats-new");
+    }
+
+    #[test]
+    fn test_slugify_collapses_whitespace() {
+        assert_eq!(slugify("Too   many   spaces"), "too-many-spaces");
+    }
+
+    #[test]
+    fn test_assign_heading_ids_dedup() {
+        let html = "<h1>Intro</h1><h2>Intro</h2>";
+        let (out, headings) = assign_heading_ids(html);
+        assert!(out.contains("<h1 id=\"intro\">"));
+        assert!(out.contains("<h2 id=\"intro-1\">"));
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[0].level, 1);
+        assert_eq!(headings[1].id, "intro-1");
+    }
+
+    #[test]
+    fn test_allowlist_mode_keeps_allowed_tags_through_full_pipeline() {
+        let options = ParserOptions {
+            html_mode: HtmlMode::Allowlist,
+            ..ParserOptions::default()
+        };
+        let input = "<b>bold</b> and <script>alert(1)</script> plain";
+        let sanitized = crate::sanitizer::sanitize_with(input, options.html_mode);
+        let html = parse_to_html(&sanitized, &options);
+        assert!(html.contains("<b>bold</b>"));
+        assert!(!html.contains("raw HTML omitted"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_escape_all_mode_still_strips_raw_html() {
+        let options = ParserOptions::default();
+        let input = "<b>bold</b>";
+        let sanitized = crate::sanitizer::sanitize_with(input, options.html_mode);
+        let html = parse_to_html(&sanitized, &options);
+        assert!(!html.contains("<b>bold</b>"));
+        assert!(html.contains("&lt;b&gt;"));
+    }
+
+    #[test]
+    fn test_parse_to_html_with_headings() {
+        let options = ParserOptions::default();
+        let (html, headings) = parse_to_html_with_headings("# Hello World", &options);
+        assert!(html.contains("id=\"hello-world\""));
+        assert_eq!(headings.len(), 1);
+        assert_eq!(headings[0].text, "Hello World");
+    }
+
+    #[test]
+    fn test_rewrite_image_proxies_remote_url() {
+        let html = r#"<p><img src="https://example.com/a.png" alt="x"></p>"#;
+        let (out, rewritten) = rewrite_image_proxies(html, "https://proxy.example/img");
+        assert_eq!(
+            out,
+            r#"<p><img src="https://proxy.example/img?url=https%3A%2F%2Fexample.com%2Fa.png" alt="x"></p>"#
+        );
+        assert_eq!(rewritten, vec!["https://example.com/a.png".to_string()]);
+    }
+
+    #[test]
+    fn test_rewrite_image_proxies_leaves_relative_urls() {
+        let html = r#"<img src="/local/a.png">"#;
+        let (out, rewritten) = rewrite_image_proxies(html, "https://proxy.example/img");
+        assert_eq!(out, html);
+        assert!(rewritten.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_image_proxies_ignores_src_look_alike_in_earlier_attribute() {
+        let html = r#"<img alt="src=" src="https://example.com/a.png">"#;
+        let (out, rewritten) = rewrite_image_proxies(html, "https://proxy.example/img");
+        assert_eq!(
+            out,
+            r#"<img alt="src=" src="https://proxy.example/img?url=https%3A%2F%2Fexample.com%2Fa.png">"#
+        );
+        assert_eq!(rewritten, vec!["https://example.com/a.png".to_string()]);
+    }
+
+    #[test]
+    fn test_rewrite_image_proxies_ignores_lookalike_tags() {
+        let html = "<imgur>not an image tag</imgur>";
+        let (out, rewritten) = rewrite_image_proxies(html, "https://proxy.example/img");
+        assert_eq!(out, html);
+        assert!(rewritten.is_empty());
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(
+            percent_encode("https://example.com/a.png"),
+            "https%3A%2F%2Fexample.com%2Fa.png"
+        );
+    }
+}
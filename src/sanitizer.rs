@@ -5,6 +5,240 @@
 
 use std::borrow::Cow;
 
+/// Controls how [`sanitize_with`] handles raw HTML in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HtmlMode {
+    /// Escape every `<`/`>` (see [`sanitize`]). The default: safest, and
+    /// what every wiki page got before [`HtmlMode::Allowlist`] existed.
+    #[default]
+    EscapeAll,
+    /// Parse the input as an HTML fragment and keep only a fixed allowlist
+    /// of elements/attributes (see [`sanitize_allowlist`]), dropping
+    /// everything else. An opt-in middle ground for authors migrating
+    /// content that relies on a handful of inline HTML tags.
+    Allowlist,
+}
+
+/// Sanitize `input` according to `mode`.
+pub fn sanitize_with(input: &str, mode: HtmlMode) -> Cow<'_, str> {
+    match mode {
+        HtmlMode::EscapeAll => sanitize(input),
+        HtmlMode::Allowlist => Cow::Owned(sanitize_allowlist(input)),
+    }
+}
+
+/// Elements kept by [`sanitize_allowlist`], and the attributes allowed on
+/// each. Tags not listed here are dropped, but their text content is kept.
+const ALLOWED_ELEMENTS: &[(&str, &[&str])] = &[
+    ("b", &[]),
+    ("i", &[]),
+    ("em", &[]),
+    ("strong", &[]),
+    ("a", &["href", "title"]),
+    ("code", &[]),
+    ("pre", &[]),
+    ("blockquote", &[]),
+    ("ul", &[]),
+    ("ol", &[]),
+    ("li", &[]),
+    ("table", &[]),
+    ("tr", &[]),
+    ("td", &[]),
+    ("th", &[]),
+    ("img", &["src", "alt", "title"]),
+];
+
+/// Schemes permitted in `href`/`src` attribute values kept by
+/// [`sanitize_allowlist`]. A value with no scheme at all (relative URLs)
+/// is always allowed.
+const ALLOWED_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Parse `input` as an HTML fragment and keep only [`ALLOWED_ELEMENTS`],
+/// dropping any other tag (its text children are preserved). Surviving
+/// elements keep only allowlisted attributes, and `href`/`src` values with
+/// an unrecognized scheme (e.g. `javascript:`, `data:`) are stripped.
+///
+/// Tag boundaries are found with a plain `<`/`>` scan rather than a true
+/// DOM walk, so a `>` inside a quoted attribute value would (incorrectly)
+/// end the tag early. This isn't currently exploitable — output always
+/// re-escapes and re-quotes attribute values rather than splicing the
+/// original quoting through — but a spec-compliant tokenizer would close
+/// this class of bug at the root instead of relying on that invariant.
+///
+/// # Examples
+///
+/// ```
+/// use lukiwiki_parser::sanitizer::sanitize_allowlist;
+///
+/// let input = "<b>bold</b> <script>alert(1)</script> plain";
+/// let output = sanitize_allowlist(input);
+/// assert_eq!(output, "<b>bold</b> alert(1) plain");
+/// ```
+pub fn sanitize_allowlist(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&sanitize(&rest[..lt]));
+        let after = &rest[lt + 1..];
+        let Some(gt) = after.find('>') else {
+            // Unterminated tag: treat the rest as plain text.
+            out.push_str(&sanitize(&rest[lt..]));
+            rest = "";
+            break;
+        };
+
+        if let Some(rendered) = render_allowed_tag(&after[..gt]) {
+            out.push_str(&rendered);
+        }
+        rest = &after[gt + 1..];
+    }
+    out.push_str(&sanitize(rest));
+
+    out
+}
+
+/// Render a single allowlisted tag, or `None` if it should be dropped.
+/// `tag_src` is the tag's source with the surrounding `<`/`>` removed.
+fn render_allowed_tag(tag_src: &str) -> Option<String> {
+    let trimmed = tag_src.trim();
+    let is_closing = trimmed.starts_with('/');
+    let body = if is_closing { &trimmed[1..] } else { trimmed };
+    let self_closing = body.trim_end().ends_with('/');
+    let body = if self_closing {
+        body.trim_end().strip_suffix('/').unwrap_or(body).trim_end()
+    } else {
+        body
+    };
+
+    let name_end = body.find(|c: char| c.is_whitespace()).unwrap_or(body.len());
+    let name = body[..name_end].to_ascii_lowercase();
+    let allowed_attrs = ALLOWED_ELEMENTS
+        .iter()
+        .find(|(element, _)| *element == name)?
+        .1;
+
+    if is_closing {
+        return Some(format!("</{name}>"));
+    }
+
+    let mut attrs = String::new();
+    for (attr_name, attr_value) in parse_attributes(&body[name_end..]) {
+        let attr_name = attr_name.to_ascii_lowercase();
+        if !allowed_attrs.contains(&attr_name.as_str()) {
+            continue;
+        }
+        if (attr_name == "href" || attr_name == "src") && !is_safe_url(&attr_value) {
+            continue;
+        }
+        attrs.push(' ');
+        attrs.push_str(&attr_name);
+        attrs.push_str("=\"");
+        attrs.push_str(&attr_value.replace('"', "&quot;"));
+        attrs.push('"');
+    }
+
+    Some(if self_closing {
+        format!("<{name}{attrs} />")
+    } else {
+        format!("<{name}{attrs}>")
+    })
+}
+
+/// Parse `name="value"` / `name='value'` / `name=value` / bare `name`
+/// attribute syntax from `s` (the tag source after the element name).
+fn parse_attributes(s: &str) -> Vec<(String, String)> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut attrs = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        let name_start = i;
+        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '-' || chars[i] == '_')
+        {
+            i += 1;
+        }
+        if i == name_start {
+            i += 1; // skip a stray character (e.g. a lone '/')
+            continue;
+        }
+        let name: String = chars[name_start..i].iter().collect();
+
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+
+        let mut value = String::new();
+        if i < chars.len() && chars[i] == '=' {
+            i += 1;
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == '"' || chars[i] == '\'') {
+                let quote = chars[i];
+                i += 1;
+                let value_start = i;
+                while i < chars.len() && chars[i] != quote {
+                    i += 1;
+                }
+                value = chars[value_start..i].iter().collect();
+                if i < chars.len() {
+                    i += 1; // past closing quote
+                }
+            } else {
+                let value_start = i;
+                while i < chars.len() && !chars[i].is_whitespace() {
+                    i += 1;
+                }
+                value = chars[value_start..i].iter().collect();
+            }
+        }
+
+        attrs.push((name, value));
+    }
+
+    attrs
+}
+
+/// Check whether `value`'s URL scheme (if any) is in [`ALLOWED_URL_SCHEMES`].
+/// Values with no scheme (relative URLs, fragments) are always safe.
+///
+/// Mirrors the WHATWG URL parser's leniency around whitespace before
+/// sniffing the scheme: browsers strip leading/trailing C0-control-or-space
+/// and remove ASCII tab/CR/LF from anywhere in the string, so
+/// `"java\tscript:alert(1)"` and `" javascript:alert(1)"` are both just
+/// `javascript:alert(1)` to them. Sniffing the raw value instead would make
+/// those look like they have no scheme at all, and an absent scheme is
+/// always treated as safe — so we normalize first, the same way.
+fn is_safe_url(value: &str) -> bool {
+    let trimmed = value.trim_matches(|c: char| c.is_ascii_control() || c == ' ');
+    let normalized: String = trimmed
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+
+    let Some(colon) = normalized.find(':') else {
+        return true;
+    };
+    let scheme = &normalized[..colon];
+    let looks_like_scheme = scheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+
+    if !looks_like_scheme {
+        return true; // the colon isn't a URI scheme separator
+    }
+
+    ALLOWED_URL_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str())
+}
+
 /// Sanitizes input text by escaping HTML tags while preserving HTML entities
 ///
 /// # Arguments
@@ -43,8 +277,12 @@ pub fn sanitize(input: &str) -> Cow<'_, str> {
             '<' => result.push_str("&lt;"),
             '>' => result.push_str("&gt;"),
             '&' => {
-                // Check if this is an HTML entity
-                if is_html_entity(&mut chars.clone()) {
+                // Check if this is a `;`-terminated entity, or one of the
+                // legacy entities HTML5 also recognizes without a trailing
+                // `;` (see `matches_legacy_entity_prefix`).
+                if is_html_entity(&mut chars.clone())
+                    || matches_legacy_entity_prefix(&mut chars.clone())
+                {
                     // Preserve the entity
                     result.push(ch);
                 } else {
@@ -96,66 +334,161 @@ fn is_valid_entity(entity: &str) -> bool {
         return false;
     }
 
-    // Numeric entities
-    if entity.starts_with('#') {
-        if entity.len() < 2 {
-            return false;
+    if let Some(numeric) = entity.strip_prefix('#') {
+        return is_valid_numeric_entity(numeric);
+    }
+
+    NAMED_ENTITIES.contains(&entity)
+}
+
+/// Whether the characters following `&` start with one of [`LEGACY_ENTITIES`]
+/// — the HTML5 "legacy" names that are recognized even without a trailing
+/// `;`, for backward compatibility with pre-HTML5 markup (e.g. bare
+/// `&nbsp` in the middle of a sentence). Matches the longest legacy name
+/// that prefixes the input, mirroring how browsers tokenize these.
+fn matches_legacy_entity_prefix(chars: &mut std::iter::Peekable<std::str::Chars>) -> bool {
+    const MAX_LEGACY_LEN: usize = 6;
+
+    let mut candidate = String::with_capacity(MAX_LEGACY_LEN);
+    while candidate.len() < MAX_LEGACY_LEN {
+        match chars.peek() {
+            Some(&c) if c.is_ascii_alphanumeric() => candidate.push(c),
+            _ => break,
         }
-        if entity[1..].starts_with('x') || entity[1..].starts_with('X') {
-            // Hexadecimal: &#xHH;
-            if entity.len() < 3 {
-                return false;
-            }
-            return entity[2..].chars().all(|c| c.is_ascii_hexdigit());
-        } else {
-            // Decimal: &#123;
-            return entity[1..].chars().all(|c| c.is_ascii_digit());
-        }
-    }
-
-    // Named entities - common ones
-    // Full list: https://html.spec.whatwg.org/multipage/named-characters.html
-    matches!(
-        entity,
-        "nbsp"
-            | "lt"
-            | "gt"
-            | "amp"
-            | "quot"
-            | "apos"
-            | "copy"
-            | "reg"
-            | "trade"
-            | "ndash"
-            | "mdash"
-            | "lsquo"
-            | "rsquo"
-            | "ldquo"
-            | "rdquo"
-            | "hellip"
-            | "prime"
-            | "Prime"
-            | "euro"
-            | "yen"
-            | "pound"
-            | "cent"
-            | "times"
-            | "divide"
-            | "plusmn"
-            | "minus"
-            | "alpha"
-            | "beta"
-            | "gamma"
-            | "delta"
-            | "epsilon"
-            | "Alpha"
-            | "Beta"
-            | "Gamma"
-            | "Delta"
-            | "Epsilon" // Add more as needed
-    )
+        chars.next();
+    }
+
+    while !candidate.is_empty() {
+        if LEGACY_ENTITIES.contains(&candidate.as_str()) {
+            return true;
+        }
+        candidate.pop();
+    }
+
+    false
 }
 
+/// Validate a numeric character reference's digits (without `&#`/`&#x` and
+/// `;`) and the code point they encode.
+///
+/// Parsing the code point (rather than just checking the digits are
+/// well-formed) lets us reject references that are well-formed but unsafe
+/// to pass through unescaped: `&#0;`, a lone surrogate half like
+/// `&#xD800;`, or an out-of-range value like `&#x110000;`. See
+/// [`is_safe_code_point`].
+fn is_valid_numeric_entity(numeric: &str) -> bool {
+    let code = match numeric.strip_prefix('x').or_else(|| numeric.strip_prefix('X')) {
+        Some(hex) if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            u32::from_str_radix(hex, 16).ok()
+        }
+        Some(_) => None,
+        None if !numeric.is_empty() && numeric.chars().all(|c| c.is_ascii_digit()) => {
+            numeric.parse::<u32>().ok()
+        }
+        None => None,
+    };
+
+    code.is_some_and(is_safe_code_point)
+}
+
+/// Whether `code` is a Unicode code point that's safe to emit as a numeric
+/// character reference: not `0`, not a C0/C1 control character (other than
+/// tab, newline, and carriage return), not a surrogate half, and within
+/// the Unicode range.
+fn is_safe_code_point(code: u32) -> bool {
+    if code == 0 || code > 0x10_FFFF {
+        return false;
+    }
+    if (0xD800..=0xDFFF).contains(&code) {
+        return false; // lone surrogate half
+    }
+    let is_c0_control = code <= 0x1F && !matches!(code, 0x09 | 0x0A | 0x0D);
+    let is_c1_or_del = code == 0x7F || (0x80..=0x9F).contains(&code);
+    !is_c0_control && !is_c1_or_del
+}
+
+/// Named HTML character references recognized by [`is_valid_entity`].
+///
+/// This is the standard HTML4/XHTML1 named entity set (Latin-1 supplement,
+/// symbols, math operators, and Greek letters); the handful of additions
+/// (`apos`, `OElig`/`oelig`, `Scaron`/`scaron`, `Yuml`, `fnof`, `circ`,
+/// `tilde`) carried forward into HTML5; an "ASCII punctuation" category
+/// (`num`, `sol`, `excl`, ...) and a small Dingbats category (`check`,
+/// `cross`, `star`) from the full WHATWG table; plus, via
+/// [`matches_legacy_entity_prefix`], the ~106 legacy names HTML5 still
+/// accepts without a trailing `;`.
+///
+/// This is *not* the complete ~2200-entry WHATWG named-character-reference
+/// table — this crate doesn't vendor that table, so entries were added for
+/// the categories above plus anything a reviewer flagged as missing.
+/// Any name still missing renders as the harmless (if unexpected)
+/// `&amp;name;` rather than leaking unescaped markup, so the gap is a
+/// fidelity issue, not a security one; if you hit one in the wild, add it
+/// here rather than routing around this table.
+const NAMED_ENTITIES: &[&str] = &[
+    // Markup-significant characters
+    "quot", "amp", "apos", "lt", "gt",
+    // Latin-1 supplement
+    "nbsp", "iexcl", "cent", "pound", "curren", "yen", "brvbar", "sect", "uml", "copy", "ordf",
+    "laquo", "not", "shy", "reg", "macr", "deg", "plusmn", "sup2", "sup3", "acute", "micro",
+    "para", "middot", "cedil", "sup1", "ordm", "raquo", "frac14", "frac12", "frac34", "iquest",
+    "Agrave", "Aacute", "Acirc", "Atilde", "Auml", "Aring", "AElig", "Ccedil", "Egrave", "Eacute",
+    "Ecirc", "Euml", "Igrave", "Iacute", "Icirc", "Iuml", "ETH", "Ntilde", "Ograve", "Oacute",
+    "Ocirc", "Otilde", "Ouml", "times", "Oslash", "Ugrave", "Uacute", "Ucirc", "Uuml", "Yacute",
+    "THORN", "szlig", "agrave", "aacute", "acirc", "atilde", "auml", "aring", "aelig", "ccedil",
+    "egrave", "eacute", "ecirc", "euml", "igrave", "iacute", "icirc", "iuml", "eth", "ntilde",
+    "ograve", "oacute", "ocirc", "otilde", "ouml", "divide", "oslash", "ugrave", "uacute", "ucirc",
+    "uuml", "yacute", "thorn", "yuml",
+    // Latin Extended-A / HTML5 additions
+    "OElig", "oelig", "Scaron", "scaron", "Yuml", "fnof", "circ", "tilde",
+    // Greek
+    "Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota", "Kappa",
+    "Lambda", "Mu", "Nu", "Xi", "Omicron", "Pi", "Rho", "Sigma", "Tau", "Upsilon", "Phi", "Chi",
+    "Psi", "Omega", "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota",
+    "kappa", "lambda", "mu", "nu", "xi", "omicron", "pi", "rho", "sigmaf", "sigma", "tau",
+    "upsilon", "phi", "chi", "psi", "omega", "thetasym", "upsih", "piv",
+    // General punctuation
+    "ensp", "emsp", "thinsp", "zwnj", "zwj", "lrm", "rlm", "ndash", "mdash", "lsquo", "rsquo",
+    "sbquo", "ldquo", "rdquo", "bdquo", "dagger", "Dagger", "bull", "hellip", "permil", "prime",
+    "Prime", "lsaquo", "rsaquo", "oline", "frasl",
+    // Letterlike / arrows
+    "euro", "image", "weierp", "real", "trade", "alefsym", "larr", "uarr", "rarr", "darr", "harr",
+    "crarr", "lArr", "uArr", "rArr", "dArr", "hArr",
+    // Mathematical operators
+    "forall", "part", "exist", "empty", "nabla", "isin", "notin", "ni", "prod", "sum", "minus",
+    "lowast", "radic", "prop", "infin", "ang", "and", "or", "cap", "cup", "int", "there4", "sim",
+    "cong", "asymp", "ne", "equiv", "le", "ge", "sub", "sup", "nsub", "sube", "supe", "oplus",
+    "otimes", "perp", "sdot",
+    // Miscellaneous technical / symbols
+    "lceil", "rceil", "lfloor", "rfloor", "lang", "rang", "loz", "spades", "clubs", "hearts",
+    "diams",
+    // ASCII punctuation / MathML compatibility aliases
+    "excl", "num", "dollar", "percnt", "lpar", "rpar", "ast", "plus", "comma", "period", "sol",
+    "colon", "semi", "equals", "quest", "commat", "lsqb", "bsol", "rsqb", "lowbar", "lcub",
+    "verbar", "rcub",
+    // Dingbats
+    "check", "cross", "star",
+];
+
+/// The HTML5 "legacy" entity names, matched by [`matches_legacy_entity_prefix`].
+/// Unlike the names in [`NAMED_ENTITIES`], these are recognized even
+/// without a trailing `;`, for compatibility with pre-HTML5 markup. Most
+/// also appear in [`NAMED_ENTITIES`]; the uppercase aliases `AMP`, `COPY`,
+/// `GT`, `LT`, `QUOT` and `REG` appear only here.
+const LEGACY_ENTITIES: &[&str] = &[
+    "AElig", "AMP", "Aacute", "Acirc", "Agrave", "Aring", "Atilde", "Auml", "COPY", "Ccedil",
+    "ETH", "Eacute", "Ecirc", "Egrave", "Euml", "GT", "Iacute", "Icirc", "Igrave", "Iuml", "LT",
+    "Ntilde", "Oacute", "Ocirc", "Ograve", "Oslash", "Otilde", "Ouml", "QUOT", "REG", "THORN",
+    "Uacute", "Ucirc", "Ugrave", "Uuml", "Yacute", "aacute", "acirc", "acute", "aelig", "agrave",
+    "amp", "aring", "atilde", "auml", "brvbar", "ccedil", "cedil", "cent", "copy", "curren", "deg",
+    "divide", "eacute", "ecirc", "egrave", "eth", "euml", "frac12", "frac14", "frac34", "gt",
+    "iacute", "icirc", "iexcl", "igrave", "iquest", "iuml", "laquo", "lt", "macr", "micro",
+    "middot", "nbsp", "not", "ntilde", "oacute", "ocirc", "ograve", "ordf", "ordm", "oslash",
+    "otilde", "ouml", "para", "plusmn", "pound", "quot", "raquo", "reg", "sect", "shy", "sup1",
+    "sup2", "sup3", "szlig", "thorn", "times", "uacute", "ucirc", "ugrave", "uml", "uuml",
+    "yacute", "yen", "yuml",
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,4 +567,143 @@ mod tests {
         assert!(!is_valid_entity("invalid"));
         assert!(!is_valid_entity(""));
     }
+
+    #[test]
+    fn test_named_entity_table_covers_common_entities() {
+        assert!(is_valid_entity("eacute"));
+        assert!(is_valid_entity("hellip"));
+        assert!(is_valid_entity("larr"));
+        assert!(is_valid_entity("alpha"));
+        assert!(is_valid_entity("apos"));
+    }
+
+    #[test]
+    fn test_numeric_entity_rejects_null() {
+        assert!(!is_valid_entity("#0"));
+        assert!(!is_valid_entity("#x0"));
+    }
+
+    #[test]
+    fn test_numeric_entity_rejects_control_characters() {
+        assert!(!is_valid_entity("#1")); // C0 control
+        assert!(!is_valid_entity("#x7F")); // DEL
+        assert!(!is_valid_entity("#x80")); // C1 control
+        // Tab, newline, and carriage return are explicitly allowed.
+        assert!(is_valid_entity("#9"));
+        assert!(is_valid_entity("#10"));
+        assert!(is_valid_entity("#13"));
+    }
+
+    #[test]
+    fn test_numeric_entity_rejects_surrogates() {
+        assert!(!is_valid_entity("#xD800"));
+        assert!(!is_valid_entity("#xDFFF"));
+        assert!(!is_valid_entity("#55296")); // 0xD800 in decimal
+    }
+
+    #[test]
+    fn test_numeric_entity_rejects_out_of_range() {
+        assert!(!is_valid_entity("#x110000"));
+        assert!(!is_valid_entity("#1114112"));
+    }
+
+    #[test]
+    fn test_sanitize_escapes_unsafe_numeric_entities() {
+        assert_eq!(sanitize("&#0;"), "&amp;#0;");
+        assert_eq!(sanitize("&#xD800;"), "&amp;#xD800;");
+        assert_eq!(sanitize("&#x110000;"), "&amp;#x110000;");
+    }
+
+    #[test]
+    fn test_sanitize_preserves_safe_named_entity() {
+        assert_eq!(sanitize("caf&eacute;"), "caf&eacute;");
+    }
+
+    #[test]
+    fn test_sanitize_preserves_whatwg_symbol_entities() {
+        assert_eq!(sanitize("&check; &cross; &star;"), "&check; &cross; &star;");
+        assert_eq!(sanitize("&num;&sol;"), "&num;&sol;");
+    }
+
+    #[test]
+    fn test_sanitize_preserves_legacy_entity_without_semicolon() {
+        assert_eq!(sanitize("Hello&nbspWorld"), "Hello&nbspWorld");
+        assert_eq!(sanitize("&ampamp"), "&ampamp");
+    }
+
+    #[test]
+    fn test_sanitize_does_not_treat_unknown_name_as_legacy_entity() {
+        assert_eq!(sanitize("&xyzxyz"), "&amp;xyzxyz");
+    }
+
+    #[test]
+    fn test_allowlist_keeps_allowed_tags() {
+        let input = "<b>bold</b> and <em>emphasis</em>";
+        assert_eq!(sanitize_allowlist(input), input);
+    }
+
+    #[test]
+    fn test_allowlist_drops_disallowed_tags_but_keeps_text() {
+        let input = "<script>alert(1)</script>plain";
+        assert_eq!(sanitize_allowlist(input), "alert(1)plain");
+    }
+
+    #[test]
+    fn test_allowlist_drops_disallowed_attributes() {
+        let input = r#"<a href="/wiki" onclick="evil()">link</a>"#;
+        assert_eq!(sanitize_allowlist(input), r#"<a href="/wiki">link</a>"#);
+    }
+
+    #[test]
+    fn test_allowlist_rejects_javascript_scheme() {
+        let input = r#"<a href="javascript:alert(1)">click</a>"#;
+        assert_eq!(sanitize_allowlist(input), "<a>click</a>");
+    }
+
+    #[test]
+    fn test_allowlist_rejects_data_scheme() {
+        let input = r#"<img src="data:text/html,evil">"#;
+        assert_eq!(sanitize_allowlist(input), "<img>");
+    }
+
+    #[test]
+    fn test_allowlist_allows_relative_and_http_urls() {
+        let input = r#"<a href="/page">x</a><a href="https://example.com">y</a>"#;
+        assert_eq!(sanitize_allowlist(input), input);
+    }
+
+    #[test]
+    fn test_allowlist_rejects_tab_obscured_javascript_scheme() {
+        let input = "<a href=\"java\tscript:alert(1)\">click</a>";
+        assert_eq!(sanitize_allowlist(input), "<a>click</a>");
+    }
+
+    #[test]
+    fn test_allowlist_rejects_leading_space_obscured_javascript_scheme() {
+        let input = r#"<a href=" javascript:alert(1)">click</a>"#;
+        assert_eq!(sanitize_allowlist(input), "<a>click</a>");
+    }
+
+    #[test]
+    fn test_allowlist_rejects_newline_obscured_javascript_scheme() {
+        let input = "<a href=\"java\nscript:alert(1)\">click</a>";
+        assert_eq!(sanitize_allowlist(input), "<a>click</a>");
+    }
+
+    #[test]
+    fn test_sanitize_with_default_is_escape_all() {
+        assert_eq!(HtmlMode::default(), HtmlMode::EscapeAll);
+        assert_eq!(
+            sanitize_with("<b>x</b>", HtmlMode::EscapeAll),
+            "&lt;b&gt;x&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_with_allowlist_mode() {
+        assert_eq!(
+            sanitize_with("<b>x</b><script>y</script>", HtmlMode::Allowlist),
+            "<b>x</b>y"
+        );
+    }
 }